@@ -0,0 +1,89 @@
+//! Fuzzes `countersyncd::actor::ipfix::{parse_template, decode_record}`
+//! directly, with no netlink socket or tokio runtime involved.
+//!
+//! Drives the decoder with a sequence of (template, data) pairs covering
+//! random set ids, field counts, enterprise-specific element ids, and
+//! mismatched record lengths, and asserts it only ever returns `Err` on
+//! malformed input instead of panicking or over-reading.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use countersyncd::actor::ipfix::{decode_record, parse_template};
+use libfuzzer_sys::fuzz_target;
+
+/// One (template bytes, data bytes) step fed through the decoder.
+#[derive(Arbitrary, Debug)]
+struct DecodeStep {
+    template_bytes: Vec<u8>,
+    data_bytes: Vec<u8>,
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    steps: Vec<DecodeStep>,
+}
+
+/// Ring buffer of recent decode steps, flushed to stderr by a panic hook
+/// so a crashing input prints the trace that led to it instead of just a
+/// bare panic message.
+mod trace_log {
+    use std::sync::{Mutex, Once, OnceLock};
+
+    const MAX_ENTRIES: usize = 64;
+
+    static LOG: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
+    static INIT: Once = Once::new();
+
+    fn log() -> &'static Mutex<Vec<String>> {
+        LOG.get_or_init(|| Mutex::new(Vec::with_capacity(MAX_ENTRIES)))
+    }
+
+    /// Install the panic hook that dumps the trace buffer. Safe to call on
+    /// every fuzz iteration; only installs once.
+    pub fn init() {
+        INIT.call_once(|| {
+            let previous = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                if let Ok(entries) = log().lock() {
+                    eprintln!("--- decode trace leading to panic ---");
+                    for entry in entries.iter() {
+                        eprintln!("{}", entry);
+                    }
+                    eprintln!("--------------------------------------");
+                }
+                previous(info);
+            }));
+        });
+    }
+
+    /// Record a step, evicting the oldest entry once the buffer is full.
+    pub fn record(entry: String) {
+        if let Ok(mut entries) = log().lock() {
+            if entries.len() >= MAX_ENTRIES {
+                entries.remove(0);
+            }
+            entries.push(entry);
+        }
+    }
+}
+
+fuzz_target!(|input: Input| {
+    trace_log::init();
+
+    for step in &input.steps {
+        trace_log::record(format!("parse_template({} bytes)", step.template_bytes.len()));
+        let template = match parse_template(&step.template_bytes) {
+            Ok(template) => template,
+            Err(_) => continue,
+        };
+
+        trace_log::record(format!(
+            "decode_record(set_id={}, {} fields, {} bytes)",
+            template.set_id,
+            template.fields.len(),
+            step.data_bytes.len()
+        ));
+        let _ = decode_record(&template, &step.data_bytes);
+    }
+});
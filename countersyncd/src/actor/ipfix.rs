@@ -0,0 +1,315 @@
+//! Decodes IPFIX templates (pushed by the `SwssActor`) and IPFIX data
+//! records (received from the `NetlinkActor`) into `SaiStats` samples.
+
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Instant;
+
+use log::{debug, info, warn};
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::time;
+
+use crate::message::{ActorName, PipelineCounters, SaiStats, WorkerStatus};
+
+const STATUS_PUBLISH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// A parsed IPFIX template: the ordered list of (field id, field length)
+/// pairs describing how to slice a matching data record.
+#[derive(Debug, Clone)]
+pub struct IpfixTemplate {
+    pub set_id: u16,
+    pub fields: Vec<(u16, u16)>,
+}
+
+/// Errors produced while decoding attacker-influenceable IPFIX bytes.
+/// Deliberately has no "panicked" variant: every malformed input must map
+/// to one of these instead.
+#[derive(Debug, PartialEq, Eq)]
+pub enum IpfixDecodeError {
+    /// Fewer bytes were available than the header/field lengths require.
+    Truncated,
+}
+
+impl std::fmt::Display for IpfixDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IpfixDecodeError::Truncated => write!(f, "truncated IPFIX record"),
+        }
+    }
+}
+
+impl std::error::Error for IpfixDecodeError {}
+
+/// Parse a raw IPFIX template set: a big-endian `set_id: u16`, a
+/// `field_count: u16`, followed by `field_count` `(field_id: u16,
+/// field_length: u16)` pairs. Bounds-checked against attacker-controlled
+/// `field_count` and truncated input; never panics or over-reads.
+pub fn parse_template(bytes: &[u8]) -> Result<IpfixTemplate, IpfixDecodeError> {
+    if bytes.len() < 4 {
+        return Err(IpfixDecodeError::Truncated);
+    }
+    let set_id = u16::from_be_bytes([bytes[0], bytes[1]]);
+    let field_count = u16::from_be_bytes([bytes[2], bytes[3]]) as usize;
+
+    let mut fields = Vec::with_capacity(field_count.min(bytes.len() / 4));
+    let mut offset = 4;
+    for _ in 0..field_count {
+        if bytes.len() < offset + 4 {
+            return Err(IpfixDecodeError::Truncated);
+        }
+        let field_id = u16::from_be_bytes([bytes[offset], bytes[offset + 1]]);
+        let field_len = u16::from_be_bytes([bytes[offset + 2], bytes[offset + 3]]);
+        fields.push((field_id, field_len));
+        offset += 4;
+    }
+
+    Ok(IpfixTemplate { set_id, fields })
+}
+
+/// Decode a raw IPFIX data record against an already-learned template.
+/// Bounds-checked against the template's declared field lengths and the
+/// record's actual length; never panics or over-reads.
+pub fn decode_record(template: &IpfixTemplate, bytes: &[u8]) -> Result<SaiStats, IpfixDecodeError> {
+    let declared_len: usize = template.fields.iter().map(|(_, len)| *len as usize).sum();
+    if bytes.len() < declared_len {
+        return Err(IpfixDecodeError::Truncated);
+    }
+
+    let (mut sai_object_id, mut stat_id, mut value) = (0u64, 0u32, 0u64);
+    let mut offset = 0;
+    for (field_id, field_len) in &template.fields {
+        let field_len = *field_len as usize;
+        let parsed = be_bytes_to_u64(&bytes[offset..offset + field_len]);
+        match field_id {
+            1 => sai_object_id = parsed,
+            2 => stat_id = parsed as u32,
+            3 => value = parsed,
+            _ => {}
+        }
+        offset += field_len;
+    }
+
+    Ok(SaiStats {
+        switch_id: "switch0".to_string(),
+        sai_object_id,
+        stat_id,
+        counter_name: stat_counter_name(stat_id),
+        value,
+        ingest_time: Instant::now(),
+    })
+}
+
+/// Resolve a SAI stat id to its counter name. Covers the `sai_port_stat_t`
+/// ids this daemon is expected to see over IPFIX; an id outside that table
+/// still gets a stable, non-empty synthetic name rather than an empty
+/// string, so downstream consumers (e.g. `render_prometheus`) never have to
+/// deal with an unnamed counter.
+fn stat_counter_name(stat_id: u32) -> String {
+    const KNOWN_PORT_STATS: &[(u32, &str)] = &[
+        (0, "SAI_PORT_STAT_IF_IN_OCTETS"),
+        (1, "SAI_PORT_STAT_IF_IN_UCAST_PKTS"),
+        (2, "SAI_PORT_STAT_IF_IN_NON_UCAST_PKTS"),
+        (3, "SAI_PORT_STAT_IF_IN_DISCARDS"),
+        (4, "SAI_PORT_STAT_IF_IN_ERRORS"),
+        (6, "SAI_PORT_STAT_IF_OUT_OCTETS"),
+        (7, "SAI_PORT_STAT_IF_OUT_UCAST_PKTS"),
+        (8, "SAI_PORT_STAT_IF_OUT_NON_UCAST_PKTS"),
+        (10, "SAI_PORT_STAT_IF_OUT_DISCARDS"),
+        (11, "SAI_PORT_STAT_IF_OUT_ERRORS"),
+    ];
+
+    KNOWN_PORT_STATS
+        .iter()
+        .find(|(id, _)| *id == stat_id)
+        .map(|(_, name)| (*name).to_string())
+        .unwrap_or_else(|| format!("SAI_STAT_UNKNOWN_{}", stat_id))
+}
+
+/// Interpret (up to) the last 8 bytes of `slice` as a big-endian integer,
+/// tolerating field lengths both shorter and longer than a `u64`.
+fn be_bytes_to_u64(slice: &[u8]) -> u64 {
+    let take = slice.len().min(8);
+    let tail = &slice[slice.len() - take..];
+    let mut buf = [0u8; 8];
+    buf[8 - take..].copy_from_slice(tail);
+    u64::from_be_bytes(buf)
+}
+
+/// Reassembles IPFIX templates and data records into `SaiStats` samples.
+///
+/// Has no shutdown signal of its own: in the pipeline's dependency order
+/// (Netlink → Ipfix → Swss → Reporter) it stops by draining `data_receiver`
+/// to completion once `NetlinkActor` exits and drops the sender side, so
+/// any raw records already buffered there are decoded and forwarded rather
+/// than dropped.
+pub struct IpfixActor {
+    template_receiver: Receiver<IpfixTemplate>,
+    data_receiver: Receiver<(u16, Vec<u8>)>,
+    templates: HashMap<u16, IpfixTemplate>,
+    recipients: Vec<Sender<SaiStats>>,
+    status_sender: Sender<(ActorName, WorkerStatus)>,
+    counters: Arc<PipelineCounters>,
+    records_seen: u64,
+    /// The most recent decode failure, surfaced via `WorkerStatus` so an
+    /// operator can see it in the status table instead of grepping logs.
+    /// Cleared the next time a record decodes successfully.
+    last_decode_error: Option<String>,
+}
+
+impl IpfixActor {
+    pub fn new(
+        template_receiver: Receiver<IpfixTemplate>,
+        data_receiver: Receiver<(u16, Vec<u8>)>,
+        status_sender: Sender<(ActorName, WorkerStatus)>,
+        counters: Arc<PipelineCounters>,
+    ) -> Self {
+        Self {
+            template_receiver,
+            data_receiver,
+            templates: HashMap::new(),
+            recipients: Vec::new(),
+            status_sender,
+            counters,
+            records_seen: 0,
+            last_decode_error: None,
+        }
+    }
+
+    /// Register a downstream consumer of decoded `SaiStats`.
+    pub fn add_recipient(&mut self, recipient: Sender<SaiStats>) {
+        self.recipients.push(recipient);
+    }
+
+    /// Apply a freshly learned template, replacing any prior definition for
+    /// the same set id.
+    fn handle_template(&mut self, template: IpfixTemplate) {
+        debug!("Learned IPFIX template for set id {}", template.set_id);
+        self.templates.insert(template.set_id, template);
+        self.counters.templates_seen.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Decode a single IPFIX data record using the currently known
+    /// templates, stamping it with the ingest time for latency accounting.
+    /// Records the outcome in `last_decode_error` so the next published
+    /// `WorkerStatus` reflects it, clearing any prior error on success.
+    fn handle_record(&mut self, set_id: u16, record: &[u8]) -> Option<SaiStats> {
+        let template = self.templates.get(&set_id)?;
+        match decode_record(template, record) {
+            Ok(stats) => {
+                self.last_decode_error = None;
+                Some(stats)
+            }
+            Err(e) => {
+                let message = format!("set id {}: {}", set_id, e);
+                warn!("Failed to decode IPFIX data record for {}", message);
+                self.last_decode_error = Some(message);
+                None
+            }
+        }
+    }
+
+    pub async fn run(mut actor: Self) {
+        info!("IpfixActor waiting for templates and data records");
+        let mut status_ticker = time::interval(STATUS_PUBLISH_INTERVAL);
+        let mut tick: u64 = 0;
+        loop {
+            tokio::select! {
+                template = actor.template_receiver.recv() => {
+                    match template {
+                        Some(template) => actor.handle_template(template),
+                        None => {
+                            info!("Template channel closed, stopping IpfixActor");
+                            break;
+                        }
+                    }
+                }
+                data = actor.data_receiver.recv() => {
+                    match data {
+                        Some((set_id, bytes)) => {
+                            actor.records_seen += 1;
+                            if let Some(stats) = actor.handle_record(set_id, &bytes) {
+                                for recipient in &actor.recipients {
+                                    if recipient.try_send(stats.clone()).is_err() {
+                                        actor.counters.records_dropped.fetch_add(1, Ordering::Relaxed);
+                                    }
+                                }
+                            }
+                        }
+                        None => {
+                            info!("Data channel closed, stopping IpfixActor");
+                            break;
+                        }
+                    }
+                }
+                _ = status_ticker.tick() => {
+                    tick += 1;
+                    let status = WorkerStatus {
+                        progress: Some(format!("{} templates, {} records seen", actor.templates.len(), actor.records_seen)),
+                        persistent_error: actor.last_decode_error.clone(),
+                        tick,
+                        ..Default::default()
+                    };
+                    let _ = actor.status_sender.try_send((ActorName::Ipfix, status));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_template_truncated_header_is_an_error() {
+        assert_eq!(parse_template(&[0x00, 0x01]).unwrap_err(), IpfixDecodeError::Truncated);
+    }
+
+    #[test]
+    fn parse_template_truncated_fields_is_an_error() {
+        // set_id=1, field_count=2, but only one field's worth of bytes follow.
+        let bytes = [0x00, 0x01, 0x00, 0x02, 0x00, 0x01, 0x00, 0x04];
+        assert_eq!(parse_template(&bytes).unwrap_err(), IpfixDecodeError::Truncated);
+    }
+
+    #[test]
+    fn parse_template_reads_all_declared_fields() {
+        // set_id=1, field_count=2: (field 1, len 4), (field 2, len 4).
+        let bytes = [0x00, 0x01, 0x00, 0x02, 0x00, 0x01, 0x00, 0x04, 0x00, 0x02, 0x00, 0x04];
+        let template = parse_template(&bytes).expect("well-formed template");
+        assert_eq!(template.set_id, 1);
+        assert_eq!(template.fields, vec![(1, 4), (2, 4)]);
+    }
+
+    #[test]
+    fn decode_record_truncated_data_is_an_error() {
+        let template = IpfixTemplate { set_id: 1, fields: vec![(1, 8), (2, 4), (3, 8)] };
+        assert_eq!(decode_record(&template, &[0u8; 4]).unwrap_err(), IpfixDecodeError::Truncated);
+    }
+
+    #[test]
+    fn decode_record_tolerates_an_oversized_field_length() {
+        // Field lengths wider than the values they carry are valid IPFIX
+        // (e.g. a counter declared as a 16-byte field); `be_bytes_to_u64`
+        // only looks at the trailing 8 bytes of each field.
+        let template = IpfixTemplate { set_id: 1, fields: vec![(1, 16), (2, 4), (3, 8)] };
+        let mut bytes = vec![0u8; 16];
+        bytes[15] = 0x2a; // sai_object_id = 42, in the low byte of a 16-byte field
+        bytes.extend_from_slice(&7u32.to_be_bytes()); // stat_id = 7
+        bytes.extend_from_slice(&9u64.to_be_bytes()); // value = 9
+
+        let stats = decode_record(&template, &bytes).expect("oversized field should still decode");
+        assert_eq!(stats.sai_object_id, 42);
+        assert_eq!(stats.stat_id, 7);
+        assert_eq!(stats.value, 9);
+        assert_eq!(stats.counter_name, "SAI_PORT_STAT_IF_OUT_UCAST_PKTS");
+    }
+
+    #[test]
+    fn stat_counter_name_maps_known_ids_and_falls_back_for_unknown_ones() {
+        assert_eq!(stat_counter_name(0), "SAI_PORT_STAT_IF_IN_OCTETS");
+        assert_eq!(stat_counter_name(11), "SAI_PORT_STAT_IF_OUT_ERRORS");
+        assert_eq!(stat_counter_name(9999), "SAI_STAT_UNKNOWN_9999");
+    }
+}
@@ -0,0 +1,6 @@
+//! Actor implementations making up the counter-sync pipeline.
+
+pub mod netlink;
+pub mod ipfix;
+pub mod swss;
+pub mod stats_reporter;
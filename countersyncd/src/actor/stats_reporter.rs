@@ -0,0 +1,327 @@
+//! Periodically reports the `SaiStats` flowing through the pipeline.
+//!
+//! The actor itself only owns buffering and scheduling; how a report is
+//! actually surfaced is delegated to a `StatsWriter` implementation so new
+//! backends (console, Prometheus, a TSDB, ...) can be added without
+//! touching the reporting loop.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::info;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::time;
+
+use crate::message::{ActorName, PipelineCounters, SaiStats, WorkerStatus};
+
+#[cfg(feature = "timescale")]
+pub mod timescale;
+
+mod histogram;
+pub use histogram::{IntervalStats, LatencyHistogram};
+
+/// Configuration for the periodic reporting loop.
+pub struct StatsReporterConfig {
+    /// How often to emit a report.
+    pub interval: Duration,
+    /// Whether each listed sample also shows its SAI object id and stat id,
+    /// or only its switch, counter name, and value.
+    pub detailed: bool,
+    /// Cap on the number of samples listed per report (`None` for
+    /// unlimited).
+    pub max_stats_per_report: Option<usize>,
+}
+
+/// Per-interval counters reported alongside the latency histogram.
+#[derive(Debug, Clone, Default)]
+pub struct IntervalCounters {
+    pub records_received: u64,
+    pub records_dropped: u64,
+    pub templates_seen: u64,
+}
+
+/// A sink that a periodic report can be handed off to.
+pub trait StatsWriter: Send + 'static {
+    /// Render and emit one report built from the stats accumulated since
+    /// the last call.
+    fn write_report(&mut self, stats: &[SaiStats], config: &StatsReporterConfig);
+
+    /// Surface the latency histogram and throughput counters for the same
+    /// interval. Default no-op so writers that only care about counter
+    /// values (e.g. a future minimal backend) aren't forced to implement
+    /// it.
+    fn write_interval_metrics(&mut self, _latency: &IntervalStats, _counters: &IntervalCounters) {}
+}
+
+impl StatsWriter for Box<dyn StatsWriter> {
+    fn write_report(&mut self, stats: &[SaiStats], config: &StatsReporterConfig) {
+        (**self).write_report(stats, config);
+    }
+
+    fn write_interval_metrics(&mut self, latency: &IntervalStats, counters: &IntervalCounters) {
+        (**self).write_interval_metrics(latency, counters);
+    }
+}
+
+/// Writes reports as formatted log lines on stdout.
+pub struct ConsoleWriter;
+
+impl StatsWriter for ConsoleWriter {
+    fn write_report(&mut self, stats: &[SaiStats], config: &StatsReporterConfig) {
+        if stats.is_empty() {
+            info!("No SAI stats received in this interval");
+            return;
+        }
+
+        let shown = match config.max_stats_per_report {
+            Some(max) => &stats[..stats.len().min(max)],
+            None => stats,
+        };
+
+        info!("--- SAI stats report ({} samples) ---", stats.len());
+        for sample in shown {
+            if config.detailed {
+                info!(
+                    "{} oid=0x{:x} stat={} {} = {}",
+                    sample.switch_id, sample.sai_object_id, sample.stat_id, sample.counter_name, sample.value
+                );
+            } else {
+                info!("{} {} = {}", sample.switch_id, sample.counter_name, sample.value);
+            }
+        }
+    }
+
+    fn write_interval_metrics(&mut self, latency: &IntervalStats, counters: &IntervalCounters) {
+        info!(
+            "latency: count={} min={:?} mean={:?} p50={:?} p90={:?} p99={:?} max={:?} | received={} dropped={} templates_seen={}",
+            latency.count,
+            latency.min,
+            latency.mean,
+            latency.p50,
+            latency.p90,
+            latency.p99,
+            latency.max,
+            counters.records_received,
+            counters.records_dropped,
+            counters.templates_seen,
+        );
+    }
+}
+
+/// Snapshot of the most recently reported `SaiStats`, shared between the
+/// reporter loop (writer) and the metrics HTTP server (reader) so that a
+/// scrape never has to block on the ingest path.
+#[derive(Default)]
+pub struct MetricsSnapshot {
+    pub samples: Vec<SaiStats>,
+    pub latency: IntervalStats,
+    pub counters: IntervalCounters,
+}
+
+/// Shared handle to a `MetricsSnapshot`.
+pub type SharedMetricsSnapshot = Arc<Mutex<MetricsSnapshot>>;
+
+/// Writes reports into a shared snapshot that the `/metrics` HTTP endpoint
+/// renders in the Prometheus text exposition format on demand.
+pub struct PrometheusWriter {
+    snapshot: SharedMetricsSnapshot,
+}
+
+impl PrometheusWriter {
+    pub fn new(snapshot: SharedMetricsSnapshot) -> Self {
+        Self { snapshot }
+    }
+}
+
+impl StatsWriter for PrometheusWriter {
+    fn write_report(&mut self, stats: &[SaiStats], _config: &StatsReporterConfig) {
+        let mut snapshot = self.snapshot.lock().expect("metrics snapshot poisoned");
+        snapshot.samples = stats.to_vec();
+    }
+
+    fn write_interval_metrics(&mut self, latency: &IntervalStats, counters: &IntervalCounters) {
+        let mut snapshot = self.snapshot.lock().expect("metrics snapshot poisoned");
+        snapshot.latency = latency.clone();
+        snapshot.counters = counters.clone();
+    }
+}
+
+/// Render a `MetricsSnapshot` as Prometheus text exposition format. One
+/// `# TYPE` line is emitted per distinct counter name, followed by a sample
+/// line per SAI object carrying that counter.
+pub fn render_prometheus(snapshot: &MetricsSnapshot) -> String {
+    use std::collections::BTreeMap;
+    use std::fmt::Write;
+
+    let mut out = String::new();
+
+    // Group by `stat_id`, not the raw `counter_name` string: `stat_id` is
+    // always present and stable, while `counter_name` is only a display
+    // string (the decoder gives an id it doesn't recognize a synthetic
+    // `SAI_STAT_UNKNOWN_<id>` name). `metric_name` then guarantees a
+    // non-empty, sanitized name per group, even for a sample whose
+    // `counter_name` is empty, so a scrape never emits a blank `# TYPE` block.
+    let mut groups: BTreeMap<u32, (String, Vec<&SaiStats>)> = BTreeMap::new();
+    for sample in &snapshot.samples {
+        groups
+            .entry(sample.stat_id)
+            .or_insert_with(|| (metric_name(sample), Vec::new()))
+            .1
+            .push(sample);
+    }
+
+    for (metric, samples) in groups.values() {
+        let _ = writeln!(out, "# TYPE {} counter", metric);
+        for sample in samples {
+            let timestamp_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis();
+            let _ = writeln!(
+                out,
+                "{}{{port=\"0x{:x}\",counter=\"{}\"}} {} {}",
+                metric, sample.sai_object_id, sample.stat_id, sample.value, timestamp_ms
+            );
+        }
+    }
+
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+
+    let _ = writeln!(out, "# TYPE countersyncd_ingest_latency_seconds summary");
+    for (quantile, value) in [
+        ("0.5", snapshot.latency.p50),
+        ("0.9", snapshot.latency.p90),
+        ("0.99", snapshot.latency.p99),
+    ] {
+        let _ = writeln!(
+            out,
+            "countersyncd_ingest_latency_seconds{{quantile=\"{}\"}} {} {}",
+            quantile,
+            value.as_secs_f64(),
+            timestamp_ms
+        );
+    }
+
+    let _ = writeln!(out, "# TYPE countersyncd_records_received_total counter");
+    let _ = writeln!(out, "countersyncd_records_received_total {} {}", snapshot.counters.records_received, timestamp_ms);
+    let _ = writeln!(out, "# TYPE countersyncd_records_dropped_total counter");
+    let _ = writeln!(out, "countersyncd_records_dropped_total {} {}", snapshot.counters.records_dropped, timestamp_ms);
+    let _ = writeln!(out, "# TYPE countersyncd_templates_seen_total counter");
+    let _ = writeln!(out, "countersyncd_templates_seen_total {} {}", snapshot.counters.templates_seen, timestamp_ms);
+
+    out
+}
+
+/// The Prometheus metric name for a sample: its sanitized `counter_name` if
+/// that is non-empty, otherwise a synthetic but still non-empty name
+/// derived from `stat_id` so an unrecognized counter is still scraped under
+/// a valid, distinct metric rather than folded into a blank `# TYPE` line.
+fn metric_name(sample: &SaiStats) -> String {
+    if sample.counter_name.is_empty() {
+        format!("sai_stat_{}", sample.stat_id)
+    } else {
+        sanitize_metric_name(&sample.counter_name)
+    }
+}
+
+/// Prometheus metric names may only contain `[a-zA-Z0-9_:]`.
+fn sanitize_metric_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == ':' { c } else { '_' })
+        .collect()
+}
+
+/// Buffers decoded `SaiStats` and flushes a report to the configured
+/// `StatsWriter` on a fixed interval.
+///
+/// Has no shutdown signal of its own: it's last in the pipeline's shutdown
+/// order (Netlink → Ipfix → Swss → Reporter), so it stops by draining
+/// `receiver` to completion once `IpfixActor` exits and drops its sender,
+/// flushing a final report for any `SaiStats` already buffered rather than
+/// dropping them.
+pub struct StatsReporterActor<W: StatsWriter> {
+    receiver: Receiver<SaiStats>,
+    config: StatsReporterConfig,
+    writer: W,
+    buffer: Vec<SaiStats>,
+    status_sender: Sender<(ActorName, WorkerStatus)>,
+    counters: Arc<PipelineCounters>,
+    latency: LatencyHistogram,
+    records_received: u64,
+}
+
+impl<W: StatsWriter> StatsReporterActor<W> {
+    pub fn new(
+        receiver: Receiver<SaiStats>,
+        config: StatsReporterConfig,
+        writer: W,
+        status_sender: Sender<(ActorName, WorkerStatus)>,
+        counters: Arc<PipelineCounters>,
+    ) -> Self {
+        Self {
+            receiver,
+            config,
+            writer,
+            buffer: Vec::new(),
+            status_sender,
+            counters,
+            latency: LatencyHistogram::new(),
+            records_received: 0,
+        }
+    }
+
+    /// Emit a report plus the interval's latency/throughput metrics, publish
+    /// status, and reset all per-interval accounting. Shared by the regular
+    /// ticker and an early shutdown-triggered flush.
+    fn flush(&mut self, tick: u64) {
+        self.writer.write_report(&self.buffer, &self.config);
+
+        let latency_stats = self.latency.take();
+        let interval_counters = IntervalCounters {
+            records_received: self.records_received,
+            records_dropped: PipelineCounters::take(&self.counters.records_dropped),
+            templates_seen: PipelineCounters::take(&self.counters.templates_seen),
+        };
+        self.writer.write_interval_metrics(&latency_stats, &interval_counters);
+        self.records_received = 0;
+
+        let status = WorkerStatus {
+            progress: Some(format!("reported {} samples", self.buffer.len())),
+            tick,
+            ..Default::default()
+        };
+        let _ = self.status_sender.try_send((ActorName::StatsReporter, status));
+        self.buffer.clear();
+    }
+
+    pub async fn run(mut actor: Self) {
+        let mut ticker = time::interval(actor.config.interval);
+        let mut tick: u64 = 0;
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    tick += 1;
+                    actor.flush(tick);
+                }
+                sample = actor.receiver.recv() => {
+                    match sample {
+                        Some(sample) => {
+                            actor.latency.record(sample.ingest_time.elapsed());
+                            actor.records_received += 1;
+                            actor.buffer.push(sample);
+                        }
+                        None => {
+                            info!("SaiStats channel closed, flushing final report");
+                            tick += 1;
+                            actor.flush(tick);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
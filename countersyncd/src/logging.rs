@@ -0,0 +1,205 @@
+//! Fan-out logging: routes every record to any number of independently
+//! leveled, independently formatted sinks (stdout, a size-rotated file,
+//! ...), replacing the single `env_logger` stdout target used previously.
+//!
+//! This is needed for long-running deployments where stdout is lost and
+//! operators want a bounded on-disk error log that's separate from the
+//! high-volume trace stream.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Level threshold for the rotated log file: only warnings and errors, so
+/// it stays small and useful even when stdout carries `debug`/`trace`.
+const LOG_FILE_LEVEL: LevelFilter = LevelFilter::Warn;
+
+/// Which line format a sink renders records with.
+#[derive(Debug, Clone, Copy)]
+pub enum LogFormat {
+    /// `[LEVEL] message`
+    Simple,
+    /// `[timestamp] [file:line] [LEVEL] message`
+    Full,
+}
+
+fn format_simple(record: &Record) -> String {
+    format!("[{}] {}", record.level(), record.args())
+}
+
+fn format_full(record: &Record) -> String {
+    format!(
+        "[{}] [{}:{}] [{}] {}",
+        chrono::Utc::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+        record.file().unwrap_or("unknown"),
+        record.line().unwrap_or(0),
+        record.level(),
+        record.args()
+    )
+}
+
+impl LogFormat {
+    fn render_fn(self) -> fn(&Record) -> String {
+        match self {
+            LogFormat::Simple => format_simple,
+            LogFormat::Full => format_full,
+        }
+    }
+}
+
+/// Settings for the process-wide logger, sourced from `--log-level`,
+/// `--log-format`, `--log-file`, `--log-rotate-size`, and
+/// `--log-rotate-keep`.
+pub struct LoggingConfig {
+    pub level: LevelFilter,
+    pub format: LogFormat,
+    /// When set, records at [`LOG_FILE_LEVEL`] and above are also written
+    /// to a size-rotated file at this path.
+    pub log_file: Option<PathBuf>,
+    /// Rotate the log file once it grows past this many bytes.
+    pub log_rotate_size: u64,
+    /// Number of rotated backups (`<path>.1`, `<path>.2`, ...) to keep.
+    pub log_rotate_keep: usize,
+}
+
+/// One output: a level filter, a formatter, and the writer it feeds.
+/// Records below `level` are dropped before formatting.
+struct Sink {
+    level: LevelFilter,
+    format: fn(&Record) -> String,
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl Sink {
+    fn log(&self, record: &Record) {
+        if record.level() > self.level {
+            return;
+        }
+        let line = (self.format)(record);
+        let mut writer = self.writer.lock().expect("log sink poisoned");
+        let _ = writeln!(writer, "{}", line);
+        let _ = writer.flush();
+    }
+}
+
+/// Fans every record out to each configured [`Sink`] independently, so
+/// (for example) stdout can carry every level while a rotated file only
+/// receives warnings and errors.
+struct FanoutLogger {
+    sinks: Vec<Sink>,
+}
+
+impl Log for FanoutLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.sinks.iter().any(|sink| metadata.level() <= sink.level)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        for sink in &self.sinks {
+            sink.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        for sink in &self.sinks {
+            let _ = sink.writer.lock().expect("log sink poisoned").flush();
+        }
+    }
+}
+
+/// A file `Write` implementation that rotates to `<path>.1`, `<path>.2`,
+/// ... once it grows past `rotate_size` bytes, discarding anything past
+/// `keep` backups.
+struct RotatingFile {
+    path: PathBuf,
+    rotate_size: u64,
+    keep: usize,
+    file: File,
+    written: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, rotate_size: u64, keep: usize) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self { path, rotate_size, keep, file, written })
+    }
+
+    fn backup_path(path: &Path, n: usize) -> PathBuf {
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(format!(".{}", n));
+        PathBuf::from(backup)
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        if self.keep == 0 {
+            self.file = OpenOptions::new().create(true).write(true).truncate(true).open(&self.path)?;
+            self.written = 0;
+            return Ok(());
+        }
+
+        let oldest = Self::backup_path(&self.path, self.keep);
+        let _ = std::fs::remove_file(&oldest);
+        for n in (1..self.keep).rev() {
+            let from = Self::backup_path(&self.path, n);
+            if from.exists() {
+                let _ = std::fs::rename(&from, Self::backup_path(&self.path, n + 1));
+            }
+        }
+        std::fs::rename(&self.path, Self::backup_path(&self.path, 1))?;
+
+        self.file = OpenOptions::new().create(true).write(true).truncate(true).open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.written >= self.rotate_size {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Install the process-wide fan-out logger: stdout at `config.level`, plus
+/// (if `config.log_file` is set) a size-rotated file at [`LOG_FILE_LEVEL`]
+/// and above.
+pub fn init(config: LoggingConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let format = config.format.render_fn();
+    let mut max_level = config.level;
+
+    let mut sinks = vec![Sink {
+        level: config.level,
+        format,
+        writer: Mutex::new(Box::new(std::io::stdout())),
+    }];
+
+    if let Some(path) = config.log_file {
+        let file_level = LOG_FILE_LEVEL.min(config.level);
+        let file = RotatingFile::open(path, config.log_rotate_size, config.log_rotate_keep)?;
+        sinks.push(Sink {
+            level: file_level,
+            format,
+            writer: Mutex::new(Box::new(file)),
+        });
+        max_level = max_level.max(file_level);
+    }
+
+    log::set_max_level(max_level);
+    log::set_boxed_logger(Box::new(FanoutLogger { sinks }))?;
+    Ok(())
+}
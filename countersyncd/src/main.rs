@@ -1,80 +1,59 @@
-// Application modules
-mod message;
-mod actor;
-
 // External dependencies
 use clap::Parser;
-use log::{error, info};
+use log::{error, info, warn};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::{spawn, sync::mpsc::channel};
 
-// Internal actor implementations
-use actor::{
-    netlink::{NetlinkActor, get_genl_family_group}, 
-    ipfix::IpfixActor,
-    stats_reporter::{StatsReporterActor, StatsReporterConfig, ConsoleWriter},
-    swss::SwssActor,
+// Internal actor implementations, provided by the `countersyncd` library
+// crate so the cargo-fuzz harness can depend on them without pulling in
+// the binary.
+use countersyncd::{
+    actor::{
+        netlink::{NetlinkActor, get_genl_family_group},
+        ipfix::IpfixActor,
+        stats_reporter::{MetricsSnapshot, PrometheusWriter, StatsReporterActor, StatsReporterConfig, ConsoleWriter, StatsWriter},
+        swss::SwssActor,
+    },
+    logging::{LogFormat, LoggingConfig},
+    message::{Command, PipelineCounters},
+    metrics_server, status,
 };
 
-/// Initialize logging based on command line arguments
-fn init_logging(log_level: &str, log_format: &str) {
-    use env_logger::{Builder, Target, WriteStyle};
+/// Build a `LoggingConfig` from command line arguments and install it as
+/// the process-wide logger.
+fn init_logging(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
     use log::LevelFilter;
-    use std::io::Write;
 
-    let level = match log_level.to_lowercase().as_str() {
+    let level = match args.log_level.to_lowercase().as_str() {
         "trace" => LevelFilter::Trace,
         "debug" => LevelFilter::Debug,
         "info" => LevelFilter::Info,
         "warn" => LevelFilter::Warn,
         "error" => LevelFilter::Error,
         _ => {
-            eprintln!("Invalid log level '{}', using 'info'", log_level);
+            eprintln!("Invalid log level '{}', using 'info'", args.log_level);
             LevelFilter::Info
         }
     };
 
-    let mut builder = Builder::new();
-    builder.filter_level(level);
-    builder.target(Target::Stdout);
-    builder.write_style(WriteStyle::Auto);
-
-    match log_format.to_lowercase().as_str() {
-        "simple" => {
-            builder.format(|buf, record| {
-                writeln!(buf, "[{}] {}", record.level(), record.args())
-            });
-        }
-        "full" => {
-            builder.format(|buf, record| {
-                writeln!(
-                    buf,
-                    "[{}] [{}:{}] [{}] {}",
-                    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S%.3f"),
-                    record.file().unwrap_or("unknown"),
-                    record.line().unwrap_or(0),
-                    record.level(),
-                    record.args()
-                )
-            });
-        }
+    let format = match args.log_format.to_lowercase().as_str() {
+        "simple" => LogFormat::Simple,
+        "full" => LogFormat::Full,
         _ => {
-            eprintln!("Invalid log format '{}', using 'full'", log_format);
-            builder.format(|buf, record| {
-                writeln!(
-                    buf,
-                    "[{}] [{}:{}] [{}] {}",
-                    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S%.3f"),
-                    record.file().unwrap_or("unknown"),
-                    record.line().unwrap_or(0),
-                    record.level(),
-                    record.args()
-                )
-            });
+            eprintln!("Invalid log format '{}', using 'full'", args.log_format);
+            LogFormat::Full
         }
-    }
+    };
 
-    builder.init();
+    countersyncd::logging::init(LoggingConfig {
+        level,
+        format,
+        log_file: args.log_file.clone(),
+        log_rotate_size: args.log_rotate_size,
+        log_rotate_keep: args.log_rotate_keep,
+    })
 }
 
 /// SONiC High Frequency Telemetry Counter Sync Daemon
@@ -113,6 +92,102 @@ struct Args {
     /// Log format (simple, full)
     #[arg(long, default_value = "full", help = "Set the log output format: 'simple' for level and message only, 'full' for timestamp, file, line, level, and message")]
     log_format: String,
+
+    /// Path to additionally log warnings and errors to, size-rotated as
+    /// `<path>`, `<path>.1`, `<path>.2`, ... A bounded on-disk error log,
+    /// kept separate from the full trace stream on stdout, for deployments
+    /// where stdout is lost.
+    #[arg(long)]
+    log_file: Option<std::path::PathBuf>,
+
+    /// Rotate `--log-file` once it grows past this many bytes.
+    #[arg(long, default_value = "10485760")]
+    log_rotate_size: u64,
+
+    /// Number of rotated `--log-file` backups to keep.
+    #[arg(long, default_value = "5")]
+    log_rotate_keep: usize,
+
+    /// Address to serve Prometheus-format `/metrics` on (e.g. 0.0.0.0:9101).
+    /// When set, stats reports are accumulated into a scrape snapshot
+    /// instead of being printed to the console.
+    #[arg(long)]
+    metrics_listen: Option<SocketAddr>,
+
+    /// PostgreSQL/TimescaleDB connection URL to stream stats into. Requires
+    /// the `timescale` feature.
+    #[cfg(feature = "timescale")]
+    #[arg(long)]
+    tsdb_url: Option<String>,
+
+    /// Number of samples per batched INSERT into TimescaleDB.
+    #[cfg(feature = "timescale")]
+    #[arg(long, default_value = "500")]
+    tsdb_batch_size: usize,
+
+    /// How often (seconds) to flush buffered samples into TimescaleDB.
+    #[cfg(feature = "timescale")]
+    #[arg(long, default_value = "5")]
+    tsdb_flush_interval: u64,
+
+    /// Unix socket path to serve the aggregated worker status table on.
+    /// Each connection gets one rendered snapshot and is then closed. The
+    /// same table can also be dumped to the log by sending SIGUSR1.
+    #[arg(long)]
+    status_socket: Option<std::path::PathBuf>,
+
+    /// Seconds to wait for an actor to drain and stop after SIGTERM/SIGINT
+    /// before aborting it.
+    #[arg(long, default_value = "10")]
+    shutdown_timeout: u64,
+}
+
+/// Await a spawned actor task, aborting it if it hasn't stopped within
+/// `timeout` of the shutdown signal (normally because it's mid-drain and
+/// just needs more time, but this bounds that wait).
+async fn join_with_timeout(
+    label: &str,
+    handle: tokio::task::JoinHandle<()>,
+    timeout: Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let abort_handle = handle.abort_handle();
+    match tokio::time::timeout(timeout, handle).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => {
+            error!("{} actor did not stop within {:?} of the shutdown signal; aborting", label, timeout);
+            abort_handle.abort();
+            Ok(())
+        }
+    }
+}
+
+/// Pick which `StatsWriter` backend the reporter should use, preferring
+/// TimescaleDB (if configured and built in), then Prometheus, then falling
+/// back to plain console output. Also reports whether `PrometheusWriter` was
+/// the one picked, so `main` knows whether the `--metrics-listen` HTTP
+/// server it may spawn will actually have anything to serve.
+async fn select_stats_writer(
+    args: &Args,
+    metrics_snapshot: &countersyncd::actor::stats_reporter::SharedMetricsSnapshot,
+) -> Result<(Box<dyn StatsWriter>, bool), Box<dyn std::error::Error>> {
+    #[cfg(feature = "timescale")]
+    if let Some(url) = &args.tsdb_url {
+        let tsdb_config = countersyncd::actor::stats_reporter::timescale::TimescaleConfig {
+            url: url.clone(),
+            batch_size: args.tsdb_batch_size,
+            flush_interval: Duration::from_secs(args.tsdb_flush_interval),
+            queue_capacity: args.tsdb_batch_size * 4,
+        };
+        let (writer, _flush_handle) = countersyncd::actor::stats_reporter::timescale::spawn(tsdb_config).await?;
+        info!("Streaming SAI stats to TimescaleDB");
+        return Ok((Box::new(writer), false));
+    }
+
+    Ok(match args.metrics_listen {
+        Some(_) => (Box::new(PrometheusWriter::new(metrics_snapshot.clone())) as Box<dyn StatsWriter>, true),
+        None => (Box::new(ConsoleWriter), false),
+    })
 }
 
 #[tokio::main]
@@ -121,8 +196,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
     
     // Initialize logging based on command line arguments
-    init_logging(&args.log_level, &args.log_format);
-    
+    init_logging(&args)?;
+
     info!("Starting SONiC High Frequency Telemetry Counter Sync Daemon");
     info!("Stats reporting enabled: {}", args.enable_stats);
     if args.enable_stats {
@@ -132,24 +207,46 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Create communication channels between actors
-    let (_command_sender, command_receiver) = channel(1);
     let (socket_sender, socket_receiver) = channel(1);
     let (ipfix_template_sender, ipfix_template_receiver) = channel(1);
     let (saistats_sender, saistats_receiver) = channel(100); // Increased buffer for stats
 
+    // Control channels for the two actors that have no upstream data channel
+    // to drain-and-exit on: `NetlinkActor` is signaled directly on
+    // SIGTERM/SIGINT, and `SwssActor` is signaled by `main` once `IpfixActor`
+    // has finished. `IpfixActor` and `StatsReporterActor` need no channel of
+    // their own — they stop by draining their upstream channel to
+    // completion once its sender side is dropped, which is what actually
+    // implements the requested drain order (Netlink → Ipfix → Swss →
+    // Reporter) instead of one signal everyone reacts to at once.
+    let (netlink_shutdown_sender, netlink_shutdown_receiver) = channel::<Command>(1);
+    let (swss_shutdown_sender, swss_shutdown_receiver) = channel::<Command>(1);
+
+    // Status channel: every actor periodically publishes a `WorkerStatus`
+    // here; the aggregator task folds them into a shared table rendered on
+    // SIGUSR1 or a `--status-socket` connection.
+    let (status_sender, status_receiver) = channel(32);
+    let status_table: status::SharedStatusTable = Arc::new(Mutex::new(std::collections::HashMap::new()));
+    spawn(status::run_aggregator(status_receiver, status_table.clone()));
+
     // Get netlink family and group configuration from SONiC constants
     let (family, group) = get_genl_family_group();
     info!("Using netlink family: '{}', group: '{}'", family, group);
 
     // Initialize and configure actors
-    let mut netlink = NetlinkActor::new(family.as_str(), group.as_str(), command_receiver);
+    let mut netlink = NetlinkActor::new(family.as_str(), group.as_str(), netlink_shutdown_receiver, status_sender.clone());
     netlink.add_recipient(socket_sender);
-    
-    let mut ipfix = IpfixActor::new(ipfix_template_receiver, socket_receiver);
+
+    // Counters shared between the IpfixActor (writer) and StatsReporterActor
+    // (reader, once per report interval): templates learned and records
+    // dropped because the downstream channel was full.
+    let pipeline_counters = PipelineCounters::shared();
+
+    let mut ipfix = IpfixActor::new(ipfix_template_receiver, socket_receiver, status_sender.clone(), pipeline_counters.clone());
     ipfix.add_recipient(saistats_sender);
 
     // Initialize SwssActor to monitor SONiC orchestrator messages
-    let swss = match SwssActor::new(ipfix_template_sender) {
+    let swss = match SwssActor::new(ipfix_template_sender, swss_shutdown_receiver, status_sender.clone()) {
         Ok(actor) => actor,
         Err(e) => {
             error!("Failed to initialize SwssActor: {}", e);
@@ -157,24 +254,121 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
+    // Shared snapshot used by the metrics HTTP server; only populated when
+    // `--metrics-listen` selects the Prometheus writer below.
+    let metrics_snapshot = Arc::new(Mutex::new(MetricsSnapshot::default()));
+
     // Configure stats reporter with settings from command line arguments
+    let mut prometheus_writer_selected = false;
     let stats_reporter = if args.enable_stats {
         let reporter_config = StatsReporterConfig {
             interval: Duration::from_secs(args.stats_interval),
             detailed: args.detailed_stats,
-            max_stats_per_report: if args.max_stats_per_report == 0 { 
-                None 
-            } else { 
-                Some(args.max_stats_per_report as usize) 
+            max_stats_per_report: if args.max_stats_per_report == 0 {
+                None
+            } else {
+                Some(args.max_stats_per_report as usize)
             },
         };
-        Some(StatsReporterActor::new(saistats_receiver, reporter_config, ConsoleWriter))
+        let (writer, is_prometheus) = select_stats_writer(&args, &metrics_snapshot).await?;
+        prometheus_writer_selected = is_prometheus;
+        Some(StatsReporterActor::new(saistats_receiver, reporter_config, writer, status_sender.clone(), pipeline_counters.clone()))
     } else {
         // Drop the receiver if stats reporting is disabled
         drop(saistats_receiver);
         None
     };
 
+    // Spawn the Prometheus scrape endpoint as its own task, but only if
+    // `select_stats_writer` actually picked `PrometheusWriter` above --
+    // otherwise `metrics_snapshot` is never populated and the endpoint would
+    // silently serve an empty scrape forever (e.g. `--tsdb-url` took
+    // priority, or `--enable-stats` is false so no writer was built at all).
+    // `tiny_http`'s accept loop is blocking, so it runs on a blocking thread
+    // rather than directly on the async runtime.
+    let _metrics_handle = match args.metrics_listen {
+        Some(listen_addr) if prometheus_writer_selected => {
+            let snapshot = metrics_snapshot.clone();
+            Some(spawn(async move {
+                tokio::task::spawn_blocking(move || metrics_server::serve(listen_addr, snapshot))
+                    .await
+                    .expect("metrics server task panicked");
+            }))
+        }
+        Some(_) => {
+            warn!(
+                "--metrics-listen was given but the Prometheus writer was not selected \
+                 (another exporter took priority, or --enable-stats is false); \
+                 the metrics HTTP endpoint will not be started"
+            );
+            None
+        }
+        None => {
+            info!("Metrics HTTP endpoint disabled (no --metrics-listen given)");
+            None
+        }
+    };
+
+    // Dump the worker status table to the log whenever SIGUSR1 arrives.
+    {
+        let status_table = status_table.clone();
+        spawn(async move {
+            let mut signal = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined1()) {
+                Ok(signal) => signal,
+                Err(e) => {
+                    error!("Failed to install SIGUSR1 handler: {}", e);
+                    return;
+                }
+            };
+            while signal.recv().await.is_some() {
+                info!("Worker status (SIGUSR1):\n{}", status::render_table(&status_table));
+            }
+        });
+    }
+
+    // Serve the same status table over a control socket if requested.
+    if let Some(socket_path) = args.status_socket.clone() {
+        let status_table = status_table.clone();
+        spawn(async move {
+            tokio::task::spawn_blocking(move || status::serve_socket(socket_path, status_table))
+                .await
+                .expect("status server task panicked");
+        });
+    }
+
+    // On SIGTERM/SIGINT, signal only NetlinkActor, the root of the pipeline's
+    // shutdown order. The rest of the chain (Ipfix, then Swss, then
+    // Reporter) is driven from the final await sequence below as each
+    // actor ahead of it actually finishes, rather than everyone reacting to
+    // the signal at once.
+    let shutdown_requested = Arc::new(tokio::sync::Notify::new());
+    {
+        let netlink_shutdown_sender = netlink_shutdown_sender.clone();
+        let shutdown_requested = shutdown_requested.clone();
+        spawn(async move {
+            let mut sigterm = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(signal) => signal,
+                Err(e) => {
+                    error!("Failed to install SIGTERM handler: {}", e);
+                    return;
+                }
+            };
+            let mut sigint = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt()) {
+                Ok(signal) => signal,
+                Err(e) => {
+                    error!("Failed to install SIGINT handler: {}", e);
+                    return;
+                }
+            };
+            tokio::select! {
+                _ = sigterm.recv() => info!("Received SIGTERM, initiating graceful shutdown"),
+                _ = sigint.recv() => info!("Received SIGINT, initiating graceful shutdown"),
+            }
+            shutdown_requested.notify_one();
+            let _ = netlink_shutdown_sender.send(Command::Shutdown).await;
+        });
+    }
+
     info!("Starting actor tasks...");
     
     // Spawn actor tasks
@@ -208,12 +402,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         None
     };
 
-    // Wait for all actors to complete and handle any errors
-    let netlink_result = netlink_handle.await;
-    let ipfix_result = ipfix_handle.await;
-    let swss_result = swss_handle.await;
+    // Wait for actors to complete strictly in pipeline dependency order
+    // (Netlink → Ipfix → Swss → Reporter), each bounded by
+    // `--shutdown-timeout` so a stuck actor can't hang shutdown forever.
+    // Netlink is the only actor signaled directly (above); each actor after
+    // it is only told to stop once the one before it has actually finished,
+    // so in-flight data already buffered downstream is drained rather than
+    // dropped:
+    //   - Ipfix drains `data_receiver` to completion once Netlink exits and
+    //     drops its sender, then exits itself (no explicit signal needed).
+    //   - Swss has no upstream channel to drain on, so it's only signaled
+    //     here, after Ipfix (its only consumer) is done with it.
+    //   - Reporter drains `receiver` to completion once Ipfix exits and
+    //     drops its sender, flushing any buffered SaiStats before exiting
+    //     (no explicit signal needed).
+    let shutdown_timeout = Duration::from_secs(args.shutdown_timeout);
+    // Netlink runs until the shutdown signal, so its timeout only starts
+    // counting once that signal has arrived.
+    let mut netlink_handle = netlink_handle;
+    let netlink_result: Result<(), Box<dyn std::error::Error>> = tokio::select! {
+        result = &mut netlink_handle => result.map_err(Into::into),
+        _ = shutdown_requested.notified() => join_with_timeout("Netlink", netlink_handle, shutdown_timeout).await,
+    };
+    let ipfix_result = join_with_timeout("Ipfix", ipfix_handle, shutdown_timeout).await;
+    let _ = swss_shutdown_sender.send(Command::Shutdown).await;
+    let swss_result = join_with_timeout("Swss", swss_handle, shutdown_timeout).await;
     let reporter_result = if let Some(handle) = reporter_handle {
-        Some(handle.await)
+        Some(join_with_timeout("StatsReporter", handle, shutdown_timeout).await)
     } else {
         None
     };
@@ -227,19 +442,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
             (Err(e), _, _, _) => {
                 error!("Netlink actor failed: {:?}", e);
-                Err(e.into())
+                Err(e)
             }
             (_, Err(e), _, _) => {
                 error!("IPFIX actor failed: {:?}", e);
-                Err(e.into())
+                Err(e)
             }
             (_, _, Err(e), _) => {
                 error!("SWSS actor failed: {:?}", e);
-                Err(e.into())
+                Err(e)
             }
             (_, _, _, Err(e)) => {
                 error!("Stats reporter actor failed: {:?}", e);
-                Err(e.into())
+                Err(e)
             }
         }
     } else {
@@ -250,15 +465,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
             (Err(e), _, _) => {
                 error!("Netlink actor failed: {:?}", e);
-                Err(e.into())
+                Err(e)
             }
             (_, Err(e), _) => {
                 error!("IPFIX actor failed: {:?}", e);
-                Err(e.into())
+                Err(e)
             }
             (_, _, Err(e)) => {
                 error!("SWSS actor failed: {:?}", e);
-                Err(e.into())
+                Err(e)
             }
         }
     }
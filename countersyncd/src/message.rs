@@ -0,0 +1,105 @@
+//! Message types shared between the counter-sync actors.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+
+/// A single decoded SAI counter sample, produced by the `IpfixActor` and
+/// consumed by the `StatsReporterActor` (and any exporter it drives).
+#[derive(Debug, Clone)]
+pub struct SaiStats {
+    /// Name of the switch the sample originated from.
+    pub switch_id: String,
+    /// SAI object id the counter belongs to (e.g. a port oid).
+    pub sai_object_id: u64,
+    /// SAI stat id identifying the specific counter.
+    pub stat_id: u32,
+    /// Human readable counter name (e.g. `SAI_PORT_STAT_IF_IN_OCTETS`).
+    pub counter_name: String,
+    /// Counter value as reported by the ASIC.
+    pub value: u64,
+    /// Time the sample first entered the pipeline (set by `IpfixActor`),
+    /// used to compute end-to-end processing latency.
+    pub ingest_time: Instant,
+}
+
+/// Control-plane commands accepted by `NetlinkActor` and `SwssActor`, each
+/// over its own command channel.
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Ask the actor to stop; the actors downstream of it then drain their
+    /// input channels once it has exited.
+    Shutdown,
+}
+
+/// Identifies which pipeline actor a `WorkerStatus` report came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActorName {
+    Netlink,
+    Ipfix,
+    Swss,
+    StatsReporter,
+}
+
+impl std::fmt::Display for ActorName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ActorName::Netlink => "netlink",
+            ActorName::Ipfix => "ipfix",
+            ActorName::Swss => "swss",
+            ActorName::StatsReporter => "stats_reporter",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A point-in-time snapshot of what an actor is doing, published
+/// periodically over the status channel so an operator can see "what is
+/// each actor doing right now" without grepping interleaved logs.
+#[derive(Debug, Clone)]
+pub struct WorkerStatus {
+    /// Short current-activity line (e.g. "1234 records/sec").
+    pub progress: Option<String>,
+    /// Set once the actor has hit an error it cannot recover from on its
+    /// own; cleared the next time things are healthy.
+    pub persistent_error: Option<String>,
+    /// Monotonically increasing counter of status publications, bumped
+    /// once per publish so a stalled actor is visible even if `last_seen`
+    /// granularity hides it.
+    pub tick: u64,
+    /// Wall-clock time this status was published.
+    pub last_seen: SystemTime,
+}
+
+impl Default for WorkerStatus {
+    fn default() -> Self {
+        Self {
+            progress: None,
+            persistent_error: None,
+            tick: 0,
+            last_seen: SystemTime::now(),
+        }
+    }
+}
+
+/// Pipeline-wide counters that are cheaper to share as atomics than to
+/// thread through yet another channel: the `IpfixActor` owns the writes,
+/// the `StatsReporterActor` reads-and-resets them once per report.
+#[derive(Default)]
+pub struct PipelineCounters {
+    pub templates_seen: AtomicU64,
+    /// Decoded `SaiStats` that couldn't be forwarded because the
+    /// downstream channel (normally to the `StatsReporterActor`) was full.
+    pub records_dropped: AtomicU64,
+}
+
+impl PipelineCounters {
+    pub fn shared() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Read and zero a counter, for once-per-interval reporting.
+    pub fn take(counter: &AtomicU64) -> u64 {
+        counter.swap(0, Ordering::Relaxed)
+    }
+}
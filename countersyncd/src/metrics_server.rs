@@ -0,0 +1,43 @@
+//! Embedded `/metrics` HTTP endpoint serving the current `SaiStats`
+//! snapshot in the Prometheus text exposition format.
+
+use std::net::SocketAddr;
+
+use log::{error, info, warn};
+use tiny_http::{Response, Server};
+
+use crate::actor::stats_reporter::{render_prometheus, SharedMetricsSnapshot};
+
+/// Bind `listen_addr` and serve `GET /metrics` from `snapshot` until the
+/// process exits. Runs its own blocking accept loop, so it must be driven
+/// from a `spawn_blocking` task rather than directly on the async runtime.
+pub fn serve(listen_addr: SocketAddr, snapshot: SharedMetricsSnapshot) {
+    let server = match Server::http(listen_addr) {
+        Ok(server) => server,
+        Err(e) => {
+            error!("Failed to bind metrics listener on {}: {}", listen_addr, e);
+            return;
+        }
+    };
+
+    info!("Serving Prometheus metrics on http://{}/metrics", listen_addr);
+
+    for request in server.incoming_requests() {
+        if request.url() != "/metrics" {
+            let response = Response::from_string("not found").with_status_code(404);
+            if let Err(e) = request.respond(response) {
+                warn!("Failed to respond to metrics request: {}", e);
+            }
+            continue;
+        }
+
+        let body = {
+            let snapshot = snapshot.lock().expect("metrics snapshot poisoned");
+            render_prometheus(&snapshot)
+        };
+        let response = Response::from_string(body);
+        if let Err(e) = request.respond(response) {
+            warn!("Failed to respond to metrics request: {}", e);
+        }
+    }
+}
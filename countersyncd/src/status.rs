@@ -0,0 +1,89 @@
+//! Aggregates the `WorkerStatus` reports published by each actor into a
+//! single "what is everyone doing right now" table, rendered on demand
+//! (SIGUSR1 or a connection to the `--status-socket` control socket) instead of
+//! requiring operators to grep interleaved actor logs.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::os::unix::net::UnixListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use log::{error, info, warn};
+use tokio::sync::mpsc::Receiver;
+
+use crate::message::{ActorName, WorkerStatus};
+
+/// Shared table of the most recent status report from each actor.
+pub type SharedStatusTable = Arc<Mutex<HashMap<ActorName, WorkerStatus>>>;
+
+/// Drains `status_receiver` into `table` until every sender has been
+/// dropped. Intended to be spawned as its own task.
+pub async fn run_aggregator(mut status_receiver: Receiver<(ActorName, WorkerStatus)>, table: SharedStatusTable) {
+    while let Some((actor, status)) = status_receiver.recv().await {
+        table.lock().expect("status table poisoned").insert(actor, status);
+    }
+    info!("Status aggregator stopping: all actor status senders dropped");
+}
+
+/// Render the current status table as an aligned text table, one row per
+/// actor that has reported in so far.
+pub fn render_table(table: &SharedStatusTable) -> String {
+    let table = table.lock().expect("status table poisoned");
+    let mut rows: Vec<_> = table.iter().collect();
+    rows.sort_by_key(|(name, _)| name.to_string());
+
+    let mut out = String::new();
+    out.push_str(&format!(
+        "{:<16} {:<8} {:<8} {:<40} {}\n",
+        "ACTOR", "TICK", "AGE", "PROGRESS", "ERROR"
+    ));
+    for (name, status) in rows {
+        out.push_str(&format!(
+            "{:<16} {:<8} {:<8} {:<40} {}\n",
+            name.to_string(),
+            status.tick,
+            format_age(status.last_seen),
+            status.progress.clone().unwrap_or_else(|| "-".to_string()),
+            status.persistent_error.clone().unwrap_or_else(|| "-".to_string()),
+        ));
+    }
+    out
+}
+
+/// Render how long ago a status was published, e.g. `"3s"`, so a stalled
+/// actor is visible even if its `tick` is still advancing slowly.
+fn format_age(last_seen: SystemTime) -> String {
+    match SystemTime::now().duration_since(last_seen) {
+        Ok(age) => format!("{}s", age.as_secs()),
+        Err(_) => "0s".to_string(),
+    }
+}
+
+/// Serve the rendered status table on a Unix domain socket: one connection
+/// gets one snapshot and is then closed. Blocking, so must be driven from
+/// `spawn_blocking` rather than directly on the async runtime.
+pub fn serve_socket(path: PathBuf, table: SharedStatusTable) {
+    let _ = std::fs::remove_file(&path);
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Failed to bind status socket {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    info!("Serving worker status on unix socket {}", path.display());
+    for connection in listener.incoming() {
+        match connection {
+            Ok(mut stream) => {
+                let rendered = render_table(&table);
+                if let Err(e) = stream.write_all(rendered.as_bytes()) {
+                    warn!("Failed to write status to client: {}", e);
+                }
+            }
+            Err(e) => warn!("Status socket accept error: {}", e),
+        }
+    }
+}